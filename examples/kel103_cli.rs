@@ -1,17 +1,27 @@
+//! A small CLI over the scalar (`f32`) API. With the `units` feature the
+//! public API takes `uom` quantities, so this example is a no-op there.
+
+#[cfg(not(feature = "units"))]
 use clap::{ArgAction, Parser, Subcommand};
+#[cfg(not(feature = "units"))]
 use std::path::PathBuf;
 
+#[cfg(not(feature = "units"))]
 use kel103::Kel103;
 
+#[cfg(not(feature = "units"))]
 #[derive(Parser)]
 struct Args {
-    device: PathBuf,
+    /// Serial port of the device; if omitted, the first KEL103 found is used.
+    #[arg(long)]
+    device: Option<PathBuf>,
     #[arg(default_value_t = 9600)]
     baud_rate: u32,
     #[command(subcommand)]
     command: Commands,
 }
 
+#[cfg(not(feature = "units"))]
 #[derive(Subcommand)]
 enum Commands {
     DeviceInfo,
@@ -55,10 +65,19 @@ enum Commands {
     GetDynamicMode,
 }
 // --- Example Usage ---
+#[cfg(feature = "units")]
+fn main() {
+    eprintln!("the kel103_cli example targets the scalar API; rebuild without --features units");
+}
+
+#[cfg(not(feature = "units"))]
 fn main() {
     let args = Args::parse();
 
-    let mut load = Kel103::new(args.device.as_path().to_str().unwrap(), args.baud_rate).unwrap();
+    let mut load = match args.device {
+        Some(device) => Kel103::new(device.as_path().to_str().unwrap(), args.baud_rate).unwrap(),
+        None => Kel103::new_auto(args.baud_rate).unwrap(),
+    };
 
     match args.command {
         Commands::DeviceInfo => println!("{}", load.device_info().unwrap()),
@@ -0,0 +1,389 @@
+//! A single command table for the KEL103's SCPI dialect.
+//!
+//! Every request the load understands is a [`Command`]; [`Command::scpi`]
+//! renders it to the wire string, and [`Response`] parses a reply back into a
+//! strongly-typed value, stripping the `V`/`A`/`W`/`HZ`/`%` suffixes the device
+//! appends. The public API and the CLI both build on this one table rather than
+//! hand-trimming suffixes and assembling ad-hoc format strings at each call site.
+
+use crate::{KelError, Result};
+use std::fmt;
+use std::net::Ipv4Addr;
+
+/// The load's operating function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Constant Current (CC).
+    ConstantCurrent,
+    /// Constant Power (CW).
+    ConstantPower,
+    /// Constant Resistance (CR).
+    ConstantResistance,
+}
+
+impl Mode {
+    fn scpi(self) -> &'static str {
+        match self {
+            Mode::ConstantCurrent => "CC",
+            Mode::ConstantPower => "CW",
+            Mode::ConstantResistance => "CR",
+        }
+    }
+}
+
+/// A parsed dynamic-mode specification.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DynSpec {
+    /// Dynamic Constant Voltage.
+    Cv {
+        /// First voltage level (V).
+        voltage1: f32,
+        /// Second voltage level (V).
+        voltage2: f32,
+        /// Switching frequency (Hz).
+        freq: f32,
+        /// Duty cycle (%).
+        dutycycle: f32,
+    },
+    /// Dynamic Constant Current.
+    Cc {
+        /// Rising slope (A/uS).
+        slope1: f32,
+        /// Falling slope (A/uS).
+        slope2: f32,
+        /// First current level (A).
+        current1: f32,
+        /// Second current level (A).
+        current2: f32,
+        /// Switching frequency (Hz).
+        freq: f32,
+        /// Duty cycle (%).
+        dutycycle: f32,
+    },
+    /// A `:DYN?` reply whose format this driver does not recognise, kept
+    /// verbatim so a differing device response is never silently lost.
+    Raw(String),
+}
+
+impl DynSpec {
+    fn scpi(&self) -> String {
+        match self {
+            DynSpec::Raw(raw) => raw.clone(),
+            DynSpec::Cv {
+                voltage1,
+                voltage2,
+                freq,
+                dutycycle,
+            } => format!(
+                ":DYN 1,{:.3}V,{:.3}V,{:.3}HZ,{:.3}%",
+                voltage1, voltage2, freq, dutycycle
+            ),
+            DynSpec::Cc {
+                slope1,
+                slope2,
+                current1,
+                current2,
+                freq,
+                dutycycle,
+            } => format!(
+                ":DYN 2,{:.3}A/uS,{:.3}A/uS,{:.3}A,{:.3}A,{:.3}HZ,{:.3}%",
+                slope1, slope2, current1, current2, freq, dutycycle
+            ),
+        }
+    }
+}
+
+impl fmt::Display for DynSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Render without the leading ":DYN " command prefix.
+        write!(f, "{}", self.scpi().trim_start_matches(":DYN "))
+    }
+}
+
+/// Every request the KEL103 understands.
+///
+/// Query variants expect a reply (read with [`Command::is_query`]); the rest are
+/// fire-and-forget writes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// `*IDN?` — device identification.
+    Identify,
+    /// Measure the input voltage.
+    QueryVoltage,
+    /// Query the *set* (CV mode) voltage.
+    QuerySetVoltage,
+    /// Set the CV-mode voltage (V).
+    SetVoltage(f32),
+    /// Measure the input power.
+    QueryPower,
+    /// Query the *set* power.
+    QuerySetPower,
+    /// Set the CW-mode power (W).
+    SetPower(f32),
+    /// Measure the input current.
+    QueryCurrent,
+    /// Query the *set* current.
+    QuerySetCurrent,
+    /// Set the CC-mode current (A).
+    SetCurrent(f32),
+    /// Query the input enable state.
+    QueryInput,
+    /// Enable (`true`) or disable (`false`) the input.
+    Input(bool),
+    /// Select the operating function.
+    Function(Mode),
+    /// Configure dynamic mode.
+    Dynamic(DynSpec),
+    /// Query the dynamic-mode configuration.
+    QueryDynamic,
+    /// Query the device's static IP address.
+    QueryIp,
+    /// Set the device's static IP address.
+    SetIp(Ipv4Addr),
+    /// Query the subnet mask.
+    QueryMask,
+    /// Set the subnet mask.
+    SetMask(Ipv4Addr),
+    /// Query the default gateway.
+    QueryGateway,
+    /// Set the default gateway.
+    SetGateway(Ipv4Addr),
+    /// Query the UDP port the device answers on.
+    QueryPort,
+    /// Set the UDP port the device answers on.
+    SetPort(u16),
+}
+
+impl Command {
+    /// Render this command to its SCPI wire string (without the trailing newline).
+    pub fn scpi(&self) -> String {
+        match self {
+            Command::Identify => "*IDN?".to_string(),
+            Command::QueryVoltage => ":MEAS:VOLT?".to_string(),
+            Command::QuerySetVoltage => ":VOLT?".to_string(),
+            Command::SetVoltage(v) => format!(":VOLT {:.3}V", v),
+            Command::QueryPower => ":MEAS:POW?".to_string(),
+            Command::QuerySetPower => ":POW?".to_string(),
+            Command::SetPower(p) => format!(":POW {:.3}W", p),
+            Command::QueryCurrent => ":MEAS:CURR?".to_string(),
+            Command::QuerySetCurrent => ":CURR?".to_string(),
+            Command::SetCurrent(c) => format!(":CURR {:.3}A", c),
+            Command::QueryInput => ":INP?".to_string(),
+            Command::Input(true) => ":INP 1".to_string(),
+            Command::Input(false) => ":INP 0".to_string(),
+            Command::Function(mode) => format!(":FUNC {}", mode.scpi()),
+            Command::Dynamic(spec) => spec.scpi(),
+            Command::QueryDynamic => ":DYN?".to_string(),
+            Command::QueryIp => ":SYST:IP?".to_string(),
+            Command::SetIp(ip) => format!(":SYST:IP {}", ip),
+            Command::QueryMask => ":SYST:MASK?".to_string(),
+            Command::SetMask(mask) => format!(":SYST:MASK {}", mask),
+            Command::QueryGateway => ":SYST:GATE?".to_string(),
+            Command::SetGateway(gw) => format!(":SYST:GATE {}", gw),
+            Command::QueryPort => ":SYST:PORT?".to_string(),
+            Command::SetPort(port) => format!(":SYST:PORT {}", port),
+        }
+    }
+
+    /// Whether this command expects a response line.
+    pub fn is_query(&self) -> bool {
+        matches!(
+            self,
+            Command::Identify
+                | Command::QueryVoltage
+                | Command::QuerySetVoltage
+                | Command::QueryPower
+                | Command::QuerySetPower
+                | Command::QueryCurrent
+                | Command::QuerySetCurrent
+                | Command::QueryInput
+                | Command::QueryDynamic
+                | Command::QueryIp
+                | Command::QueryMask
+                | Command::QueryGateway
+                | Command::QueryPort
+        )
+    }
+}
+
+/// A raw response line from the device, awaiting typed interpretation.
+pub struct Response(String);
+
+impl Response {
+    /// Wrap a raw response line.
+    pub fn new(raw: String) -> Self {
+        Response(raw)
+    }
+
+    /// The trimmed response text, suffix included.
+    pub fn raw(&self) -> &str {
+        self.0.trim()
+    }
+
+    /// Parse the response as a scalar, stripping any `V`/`A`/`W`/`HZ`/`%` suffix.
+    pub fn scalar(&self) -> Result<f32> {
+        parse_scalar(&self.0)
+    }
+
+    /// Interpret an `:INP?` response as an enabled/disabled flag.
+    pub fn on_off(&self) -> Result<bool> {
+        if self.0.contains("OFF") {
+            Ok(false)
+        } else if self.0.contains("ON") {
+            Ok(true)
+        } else {
+            Err(KelError::DeviceError(format!(
+                "Unexpected response from :INP?: {}",
+                self.0
+            )))
+        }
+    }
+
+    /// Parse the response as an IPv4 address (for the `:SYST:*` queries).
+    pub fn ipv4(&self) -> Result<Ipv4Addr> {
+        let s = self.0.trim();
+        s.parse::<Ipv4Addr>()
+            .map_err(|e| KelError::DeviceError(format!("Invalid IPv4 in response '{}': {}", s, e)))
+    }
+
+    /// Parse the response as a UDP port number.
+    pub fn port(&self) -> Result<u16> {
+        let s = self.0.trim();
+        s.parse::<u16>()
+            .map_err(|e| KelError::DeviceError(format!("Invalid port in response '{}': {}", s, e)))
+    }
+
+    /// Parse a `:DYN?` response into a [`DynSpec`].
+    ///
+    /// The KEL103's query reply is assumed to echo the `:DYN <n>,…` set-format
+    /// field order. Because that is not guaranteed across firmware revisions,
+    /// a reply this driver cannot interpret is preserved verbatim as
+    /// [`DynSpec::Raw`] rather than reported as an error (the baseline returned
+    /// the raw string).
+    pub fn dynamic(&self) -> Result<DynSpec> {
+        let line = self.0.trim();
+        Ok(parse_dynamic(line).unwrap_or_else(|| DynSpec::Raw(line.to_string())))
+    }
+}
+
+/// Attempt to parse a `:DYN?` reply in the known set-format field order.
+fn parse_dynamic(line: &str) -> Option<DynSpec> {
+    let mut fields = line.trim_start_matches(":DYN ").split(',');
+    let mode = fields.next()?.trim().parse::<u8>().ok()?;
+    let rest: Vec<f32> = fields.map(|f| parse_scalar(f).ok()).collect::<Option<_>>()?;
+
+    match (mode, rest.as_slice()) {
+        (1, [voltage1, voltage2, freq, dutycycle]) => Some(DynSpec::Cv {
+            voltage1: *voltage1,
+            voltage2: *voltage2,
+            freq: *freq,
+            dutycycle: *dutycycle,
+        }),
+        (2, [slope1, slope2, current1, current2, freq, dutycycle]) => Some(DynSpec::Cc {
+            slope1: *slope1,
+            slope2: *slope2,
+            current1: *current1,
+            current2: *current2,
+            freq: *freq,
+            dutycycle: *dutycycle,
+        }),
+        _ => None,
+    }
+}
+
+/// Strip a single known unit suffix (`V`/`A`/`W`/`HZ`/`%`, or the `A/uS` slope
+/// tail) and parse. Only one suffix is removed, so a malformed reply such as
+/// `"3.3VA"` or `"12WW"` fails to parse rather than silently yielding a value.
+fn parse_scalar<S: AsRef<str>>(s: S) -> Result<f32> {
+    let trimmed = s.as_ref().trim();
+    let stripped = trimmed
+        .strip_suffix("A/uS")
+        .or_else(|| trimmed.strip_suffix("HZ"))
+        .or_else(|| trimmed.strip_suffix('V'))
+        .or_else(|| trimmed.strip_suffix('A'))
+        .or_else(|| trimmed.strip_suffix('W'))
+        .or_else(|| trimmed.strip_suffix('%'))
+        .unwrap_or(trimmed);
+    stripped.trim().parse::<f32>().map_err(KelError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_scalar_strips_one_known_suffix() {
+        assert_eq!(parse_scalar("3.30V").unwrap(), 3.30);
+        assert_eq!(parse_scalar("1.250A").unwrap(), 1.25);
+        assert_eq!(parse_scalar("12.000W").unwrap(), 12.0);
+        assert_eq!(parse_scalar("50.000HZ").unwrap(), 50.0);
+        assert_eq!(parse_scalar("50.0%").unwrap(), 50.0);
+        assert_eq!(parse_scalar("2.000A/uS").unwrap(), 2.0);
+        // Leading/trailing whitespace and newlines are tolerated.
+        assert_eq!(parse_scalar(" 4.2V\r\n").unwrap(), 4.2);
+    }
+
+    #[test]
+    fn parse_scalar_rejects_double_suffix() {
+        // Only a single suffix is stripped, so these remain unparseable.
+        assert!(parse_scalar("3.3VA").is_err());
+        assert!(parse_scalar("12WW").is_err());
+        assert!(parse_scalar("nonsense").is_err());
+    }
+
+    #[test]
+    fn on_off_parses_state() {
+        assert!(!Response::new("OFF\n".to_string()).on_off().unwrap());
+        assert!(Response::new("ON\n".to_string()).on_off().unwrap());
+        assert!(Response::new("maybe".to_string()).on_off().is_err());
+    }
+
+    #[test]
+    fn dynamic_parses_cv() {
+        let resp = Response::new("1,2.000V,5.000V,1.000HZ,50.0%\n".to_string());
+        assert_eq!(
+            resp.dynamic().unwrap(),
+            DynSpec::Cv {
+                voltage1: 2.0,
+                voltage2: 5.0,
+                freq: 1.0,
+                dutycycle: 50.0,
+            }
+        );
+    }
+
+    #[test]
+    fn dynamic_parses_cc() {
+        let resp =
+            Response::new("2,1.000A/uS,2.000A/uS,0.500A,1.500A,10.000HZ,25.0%".to_string());
+        assert_eq!(
+            resp.dynamic().unwrap(),
+            DynSpec::Cc {
+                slope1: 1.0,
+                slope2: 2.0,
+                current1: 0.5,
+                current2: 1.5,
+                freq: 10.0,
+                dutycycle: 25.0,
+            }
+        );
+    }
+
+    #[test]
+    fn dynamic_falls_back_to_raw() {
+        // Unrecognised mode or field count is preserved verbatim, not an error.
+        let resp = Response::new("9,whatever,fields".to_string());
+        assert_eq!(
+            resp.dynamic().unwrap(),
+            DynSpec::Raw("9,whatever,fields".to_string())
+        );
+    }
+
+    #[test]
+    fn is_query_matches_query_commands() {
+        assert!(Command::QueryVoltage.is_query());
+        assert!(Command::QueryIp.is_query());
+        assert!(!Command::SetVoltage(1.0).is_query());
+        assert!(!Command::Input(true).is_query());
+    }
+}
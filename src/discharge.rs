@@ -0,0 +1,145 @@
+//! Battery-discharge / capacity-integration test.
+//!
+//! [`Kel103::discharge_test`] is the canonical electronic-load use case: sink a
+//! constant current until the terminal voltage falls to a cutoff, integrating
+//! delivered charge (Ah) and energy (Wh) from the periodic telemetry stream,
+//! then switch the input off. It builds entirely on the CC setter and the
+//! [`Report`](crate::Report) channel from [`start_reporting`](Kel103::start_reporting).
+
+use crate::{Kel103, Report, Result, Transport};
+use std::time::Duration;
+
+/// Aggregate results of a [`discharge_test`](Kel103::discharge_test).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DischargeSummary {
+    /// Total elapsed discharge time.
+    pub elapsed: Duration,
+    /// Delivered charge, in amp-hours.
+    pub charge_ah: f32,
+    /// Delivered energy, in watt-hours.
+    pub energy_wh: f32,
+    /// Time-weighted average terminal voltage, in volts.
+    pub average_volt: f32,
+}
+
+/// A completed discharge test: its [`DischargeSummary`] and the full sample log.
+#[derive(Debug, Clone)]
+pub struct DischargeResult {
+    /// Integrated summary of the run.
+    pub summary: DischargeSummary,
+    /// Every [`Report`] collected during the run, in order.
+    pub samples: Vec<Report>,
+}
+
+impl<T: Transport + Send + 'static> Kel103<T> {
+    /// Discharge a cell at `current` amps until the terminal voltage drops to
+    /// `cutoff_volt`, sampling every `interval`.
+    ///
+    /// Charge and energy are accumulated by trapezoidal integration of the
+    /// periodic measurements. The input is switched off automatically once the
+    /// cutoff is reached.
+    pub fn discharge_test(
+        &mut self,
+        current: f32,
+        cutoff_volt: f32,
+        interval: Duration,
+    ) -> Result<DischargeResult> {
+        self.set_constant_current()?;
+        self.set_current_raw(current)?;
+        self.set_output(true)?;
+
+        let rx = self.start_reporting(interval)?;
+
+        let mut samples: Vec<Report> = Vec::new();
+        for report in rx.iter() {
+            let reached_cutoff = report.volt <= cutoff_volt;
+            samples.push(report);
+            if reached_cutoff {
+                break;
+            }
+        }
+
+        self.stop_reporting();
+        self.set_output(false)?;
+
+        Ok(DischargeResult {
+            summary: summarize(&samples),
+            samples,
+        })
+    }
+}
+
+/// Integrate a discharge sample log into a [`DischargeSummary`].
+///
+/// Charge (Ah) and energy (Wh) are trapezoidal sums over consecutive samples;
+/// `average_volt` is the time-weighted mean terminal voltage. A log with fewer
+/// than two samples has no elapsed span, so `average_volt` falls back to the
+/// single recorded voltage.
+fn summarize(samples: &[Report]) -> DischargeSummary {
+    let mut charge_ah = 0.0f32;
+    let mut energy_wh = 0.0f32;
+    let mut volt_seconds = 0.0f32;
+
+    for pair in samples.windows(2) {
+        let (prev, cur) = (&pair[0], &pair[1]);
+        let dt = (cur.t - prev.t).as_secs_f32();
+        let hours = dt / 3600.0;
+        charge_ah += 0.5 * (prev.curr + cur.curr) * hours;
+        energy_wh += 0.5 * (prev.power + cur.power) * hours;
+        volt_seconds += 0.5 * (prev.volt + cur.volt) * dt;
+    }
+
+    let first = samples.first().map(|r| r.t).unwrap_or_default();
+    let last = samples.last().map(|r| r.t).unwrap_or_default();
+    let elapsed = last.saturating_sub(first);
+    let elapsed_secs = elapsed.as_secs_f32();
+    let average_volt = if elapsed_secs > 0.0 {
+        volt_seconds / elapsed_secs
+    } else {
+        samples.last().map(|r| r.volt).unwrap_or(0.0)
+    };
+
+    DischargeSummary {
+        elapsed,
+        charge_ah,
+        energy_wh,
+        average_volt,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(t_secs: u64, volt: f32, curr: f32, power: f32) -> Report {
+        Report {
+            t: Duration::from_secs(t_secs),
+            interval: Duration::from_secs(1),
+            volt,
+            curr,
+            power,
+        }
+    }
+
+    #[test]
+    fn summarize_integrates_trapezoids() {
+        // One hour at 2 A / 20 W, terminal voltage sagging 10 V -> 8 V.
+        let samples = vec![
+            report(0, 10.0, 2.0, 20.0),
+            report(3600, 8.0, 2.0, 20.0),
+        ];
+        let summary = summarize(&samples);
+        assert_eq!(summary.elapsed, Duration::from_secs(3600));
+        assert!((summary.charge_ah - 2.0).abs() < 1e-6);
+        assert!((summary.energy_wh - 20.0).abs() < 1e-6);
+        assert!((summary.average_volt - 9.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn summarize_single_sample_reports_its_voltage() {
+        let summary = summarize(&[report(5, 3.3, 1.0, 3.3)]);
+        assert_eq!(summary.charge_ah, 0.0);
+        assert_eq!(summary.energy_wh, 0.0);
+        assert_eq!(summary.average_volt, 3.3);
+    }
+}
@@ -1,15 +1,34 @@
 #![warn(missing_docs)]
 //! A crate for controlling KEL103 Electronic Loads
-//! Currently only serial port control is supported, but adding UDP control
-//! should be simple.
-
-use serialport::{SerialPort, TTYPort};
-use std::{
-    io::{self, BufRead, BufReader},
-    time::Duration,
-};
+//! Control is supported over the USB serial port or over the LAN (UDP); see
+//! [`Transport`] for the link abstraction the command methods are built on.
+
+use std::io;
+use std::net::Ipv4Addr;
+use std::sync::{Arc, Mutex, MutexGuard};
 use thiserror::Error;
 
+#[cfg(feature = "units")]
+use uom::si::electric_current::ampere;
+#[cfg(feature = "units")]
+use uom::si::electric_potential::volt;
+#[cfg(feature = "units")]
+use uom::si::f32::{ElectricCurrent, ElectricPotential, Power};
+#[cfg(feature = "units")]
+use uom::si::power::watt;
+
+mod command;
+mod discharge;
+mod regulator;
+mod telemetry;
+mod transport;
+
+pub use command::{Command, DynSpec, Mode, Response};
+pub use discharge::{DischargeResult, DischargeSummary};
+pub use regulator::Regulator;
+pub use telemetry::Report;
+pub use transport::{SerialTransport, Transport, UdpTransport};
+
 // Define custom errors for better context
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -28,63 +47,119 @@ pub enum KelError {
     DeviceError(String),
     #[error("Device is not a KEL103")]
     DeviceModel(String),
+    #[error("No KEL103 device found on any available serial port")]
+    NoDeviceFound,
 }
 
 type Result<T> = std::result::Result<T, KelError>;
 
+/// The device's LAN (IPv4) configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanConfig {
+    /// Static IP address of the device.
+    pub ip: Ipv4Addr,
+    /// Subnet mask.
+    pub mask: Ipv4Addr,
+    /// Default gateway.
+    pub gateway: Ipv4Addr,
+    /// UDP port the device answers SCPI on.
+    pub port: u16,
+}
+
 /// Representation of a KEL103 Electronic Load
-pub struct Kel103 {
-    port_write: Box<dyn SerialPort>,
-    port_read: BufReader<TTYPort>,
+///
+/// The link is held behind an [`Arc`]/[`Mutex`] so the background telemetry
+/// thread (see [`start_reporting`](Kel103::start_reporting)) and the foreground
+/// handle share one connection, with every request/response transaction
+/// serialized — a single serial line is never driven by two callers at once.
+pub struct Kel103<T: Transport> {
+    transport: Arc<Mutex<T>>,
+    reporting: Option<telemetry::ReportHandle>,
 }
 
-impl Kel103 {
+impl Kel103<SerialTransport> {
     /// Attempt to create a KEL103 from a serial port and baud rate
     /// On Linux serial port should be a path (e.g `/dev/ttyACM0`),
     /// on windows it will be a port name (e.g `COM0`).
     pub fn new(serial_port: &str, baud_rate: u32) -> Result<Self> {
-        let port = serialport::new(serial_port, baud_rate)
-            .timeout(Duration::from_secs(1))
-            .open_native()?;
-        let (port_write, port_read) = (port.try_clone()?, BufReader::new(port));
+        Self::with_transport(SerialTransport::open(serial_port, baud_rate)?)
+    }
 
+    /// Discover a KEL103 by probing every available serial port at `baud_rate`.
+    ///
+    /// Each candidate port is opened and queried with `*IDN?`; the first one
+    /// that identifies as a KEL103 is returned. If no such device is present,
+    /// fails with [`KelError::NoDeviceFound`].
+    pub fn new_auto(baud_rate: u32) -> Result<Self> {
+        for info in serialport::available_ports()? {
+            // Skip ports that fail to open or don't identify as a KEL103.
+            if let Ok(load) = Self::new(&info.port_name, baud_rate) {
+                return Ok(load);
+            }
+        }
+        Err(KelError::NoDeviceFound)
+    }
+
+    /// Discover a KEL103 on any available serial port at the default baud rate.
+    ///
+    /// Convenience wrapper around [`new_auto`](Self::new_auto).
+    pub fn find() -> Result<Self> {
+        Self::new_auto(9600)
+    }
+}
+
+impl Kel103<UdpTransport> {
+    /// Attempt to create a KEL103 over the LAN, speaking to `addr` on UDP `port`.
+    pub fn new_udp(addr: &str, port: u16) -> Result<Self> {
+        Self::with_transport(UdpTransport::connect(addr, port)?)
+    }
+}
+
+impl<T: Transport> Kel103<T> {
+    /// Wrap an already-opened [`Transport`], verifying the device identifies as a KEL103.
+    fn with_transport(transport: T) -> Result<Self> {
         let mut this = Kel103 {
-            port_write,
-            port_read,
+            transport: Arc::new(Mutex::new(transport)),
+            reporting: None,
         };
         let info = this.device_info()?;
         if !info.contains("KEL103") {
             return Err(KelError::DeviceModel(info));
         };
-
         Ok(this)
     }
 
+    /// A shared handle to the link, for the telemetry thread to borrow.
+    pub(crate) fn transport(&self) -> Arc<Mutex<T>> {
+        Arc::clone(&self.transport)
+    }
+
+    /// Lock the shared link, recovering from a poisoned mutex so a panicked
+    /// reader thread cannot wedge the foreground handle.
+    fn lock(&self) -> MutexGuard<'_, T> {
+        self.transport.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
     /// Get device identification string.
     pub fn device_info(&mut self) -> Result<String> {
-        self.send_recv(b"*IDN?")
+        Ok(self.query(Command::Identify)?.raw().to_string())
     }
 
-    /// Measure the input voltage.
-    pub fn measure_volt(&mut self) -> Result<f32> {
-        let s = self.send_recv(b":MEAS:VOLT?")?;
-        let val_str = s.trim_end_matches(['V', '\n', '\r'].as_ref()).trim();
-        val_str.parse::<f32>().map_err(KelError::from) // Convert parse error
+    /// Measure the input voltage, in volts.
+    pub(crate) fn measure_volt_raw(&mut self) -> Result<f32> {
+        self.query(Command::QueryVoltage)?.scalar()
     }
 
-    /// Measure the *set* (CV mode) voltage level.
-    pub fn measure_set_volt(&mut self) -> Result<f32> {
-        let s = self.send_recv(b":VOLT?")?;
-        let val_str = s.trim_end_matches(['V', '\n', '\r'].as_ref()).trim();
-        val_str.parse::<f32>().map_err(KelError::from)
+    /// Measure the *set* (CV mode) voltage level, in volts.
+    pub(crate) fn measure_set_volt_raw(&mut self) -> Result<f32> {
+        self.query(Command::QuerySetVoltage)?.scalar()
     }
 
-    /// Set the voltage level (CV mode).
-    pub fn set_volt(&mut self, voltage: f32) -> Result<()> {
-        let cmd = format!(":VOLT {:.3}V", voltage); // Format voltage
-        self.send(cmd.as_bytes())?;
+    /// Set the voltage level (CV mode), in volts.
+    pub(crate) fn set_volt_raw(&mut self, voltage: f32) -> Result<()> {
+        self.issue(Command::SetVoltage(voltage))?;
         // Verification - Note: direct float comparison can be problematic
-        let set_v = self.measure_set_volt()?;
+        let set_v = self.measure_set_volt_raw()?;
         if (set_v - voltage).abs() > 1e-9 {
             // Using a small tolerance instead of !=
             return Err(KelError::ValueError(format!(
@@ -95,26 +170,21 @@ impl Kel103 {
         Ok(())
     }
 
-    /// Measure the input power.
-    pub fn measure_power(&mut self) -> Result<f32> {
-        let s = self.send_recv(b":MEAS:POW?")?;
-        let val_str = s.trim_end_matches(['W', '\n', '\r'].as_ref()).trim();
-        val_str.parse::<f32>().map_err(KelError::from)
+    /// Measure the input power, in watts.
+    pub(crate) fn measure_power_raw(&mut self) -> Result<f32> {
+        self.query(Command::QueryPower)?.scalar()
     }
 
-    /// Measure the *set* power level.
-    pub fn measure_set_power(&mut self) -> Result<f32> {
-        let s = self.send_recv(b":POW?")?;
-        let val_str = s.trim_end_matches(['W', '\n', '\r'].as_ref()).trim();
-        val_str.parse::<f32>().map_err(KelError::from)
+    /// Measure the *set* power level, in watts.
+    pub(crate) fn measure_set_power_raw(&mut self) -> Result<f32> {
+        self.query(Command::QuerySetPower)?.scalar()
     }
 
-    /// Set the power level (CW mode).
-    pub fn set_power(&mut self, power: f32) -> Result<()> {
-        let cmd = format!(":POW {:.3}W", power);
-        self.send(cmd.as_bytes())?;
+    /// Set the power level (CW mode), in watts.
+    pub(crate) fn set_power_raw(&mut self, power: f32) -> Result<()> {
+        self.issue(Command::SetPower(power))?;
         // Verification
-        let set_p = self.measure_set_power()?;
+        let set_p = self.measure_set_power_raw()?;
         if (set_p - power).abs() > 1e-9 {
             // Use tolerance
             return Err(KelError::ValueError(format!(
@@ -125,26 +195,21 @@ impl Kel103 {
         Ok(())
     }
 
-    /// Measure the input current.
-    pub fn measure_current(&mut self) -> Result<f32> {
-        let s = self.send_recv(b":MEAS:CURR?")?;
-        let val_str = s.trim_end_matches(['A', '\n', '\r'].as_ref()).trim();
-        val_str.parse::<f32>().map_err(KelError::from)
+    /// Measure the input current, in amps.
+    pub(crate) fn measure_current_raw(&mut self) -> Result<f32> {
+        self.query(Command::QueryCurrent)?.scalar()
     }
 
-    /// Measure the *set* current level.
-    pub fn measure_set_current(&mut self) -> Result<f32> {
-        let s = self.send_recv(b":CURR?")?;
-        let val_str = s.trim_end_matches(['A', '\n', '\r'].as_ref()).trim();
-        val_str.parse::<f32>().map_err(KelError::from)
+    /// Measure the *set* current level, in amps.
+    pub(crate) fn measure_set_current_raw(&mut self) -> Result<f32> {
+        self.query(Command::QuerySetCurrent)?.scalar()
     }
 
-    /// Set the current level (CC mode).
-    pub fn set_current(&mut self, current: f32) -> Result<()> {
-        let cmd = format!(":CURR {:.3}A", current);
-        self.send(cmd.as_bytes())?;
+    /// Set the current level (CC mode), in amps.
+    pub(crate) fn set_current_raw(&mut self, current: f32) -> Result<()> {
+        self.issue(Command::SetCurrent(current))?;
         // Verification
-        let set_c = self.measure_set_current()?;
+        let set_c = self.measure_set_current_raw()?;
         if (set_c - current).abs() > 1e-9 {
             // Use tolerance
             return Err(KelError::ValueError(format!(
@@ -157,23 +222,12 @@ impl Kel103 {
 
     /// Check if the input/output is enabled (ON) or disabled (OFF).
     pub fn check_output(&mut self) -> Result<bool> {
-        let s = self.send_recv(b":INP?")?;
-        if s.contains("OFF") {
-            Ok(false)
-        } else if s.contains("ON") {
-            Ok(true)
-        } else {
-            Err(KelError::DeviceError(format!(
-                "Unexpected response from :INP?: {}",
-                s
-            )))
-        }
+        self.query(Command::QueryInput)?.on_off()
     }
 
     /// Enable (true) or disable (false) the input/output.
     pub fn set_output(&mut self, state: bool) -> Result<()> {
-        let cmd = if state { b":INP 1" } else { b":INP 0" };
-        self.send(cmd)?;
+        self.issue(Command::Input(state))?;
         // Verification
         let actual_state = self.check_output()?;
         if actual_state != state {
@@ -187,17 +241,17 @@ impl Kel103 {
 
     /// Set the device mode to Constant Current (CC).
     pub fn set_constant_current(&mut self) -> Result<()> {
-        self.send(b":FUNC CC")
+        self.issue(Command::Function(Mode::ConstantCurrent))
     }
 
     /// Set the device mode to Constant Power (CW).
     pub fn set_constant_power(&mut self) -> Result<()> {
-        self.send(b":FUNC CW")
+        self.issue(Command::Function(Mode::ConstantPower))
     }
 
     /// Set the device mode to Constant Resistance (CR).
     pub fn set_constant_resistance(&mut self) -> Result<()> {
-        self.send(b":FUNC CR")
+        self.issue(Command::Function(Mode::ConstantResistance))
     }
 
     /// Set Dynamic Mode CV (Constant Voltage).
@@ -208,11 +262,12 @@ impl Kel103 {
         freq: f32,
         dutycycle: f32,
     ) -> Result<()> {
-        let cmd = format!(
-            ":DYN 1,{:.3}V,{:.3}V,{:.3}HZ,{:.3}%",
-            voltage1, voltage2, freq, dutycycle
-        );
-        self.send(cmd.as_bytes())
+        self.issue(Command::Dynamic(DynSpec::Cv {
+            voltage1,
+            voltage2,
+            freq,
+            dutycycle,
+        }))
     }
 
     /// Set Dynamic Mode CC (Constant Current).
@@ -225,40 +280,168 @@ impl Kel103 {
         freq: f32,
         dutycycle: f32,
     ) -> Result<()> {
-        let cmd = format!(
-            ":DYN 2,{:.3}A/uS,{:.3}A/uS,{:.3}A,{:.3}A,{:.3}HZ,{:.3}%",
-            slope1, slope2, current1, current2, freq, dutycycle
-        );
-        self.send(cmd.as_bytes())
+        self.issue(Command::Dynamic(DynSpec::Cc {
+            slope1,
+            slope2,
+            current1,
+            current2,
+            freq,
+            dutycycle,
+        }))
     }
 
     /// Get the current dynamic mode settings.
-    pub fn get_dynamic_mode(&mut self) -> Result<String> {
-        let s = self.send_recv(b":DYN?")?;
-        Ok(s.trim_end_matches('\n').to_string())
+    pub fn get_dynamic_mode(&mut self) -> Result<DynSpec> {
+        self.query(Command::QueryDynamic)?.dynamic()
     }
 
-    /// Sends a message and receives a response line.
-    fn send_recv(&mut self, message: &[u8]) -> Result<String> {
-        // Write message with newline
-        self.send(message)?;
+    /// Read the device's LAN (IPv4) configuration over the wire.
+    pub fn get_lan_config(&mut self) -> Result<LanConfig> {
+        Ok(LanConfig {
+            ip: self.query(Command::QueryIp)?.ipv4()?,
+            mask: self.query(Command::QueryMask)?.ipv4()?,
+            gateway: self.query(Command::QueryGateway)?.ipv4()?,
+            port: self.query(Command::QueryPort)?.port()?,
+        })
+    }
+
+    /// Reconfigure the device's LAN (IPv4) address over the wire.
+    pub fn set_lan_config(&mut self, config: &LanConfig) -> Result<()> {
+        self.issue(Command::SetIp(config.ip))?;
+        self.issue(Command::SetMask(config.mask))?;
+        self.issue(Command::SetGateway(config.gateway))?;
+        self.issue(Command::SetPort(config.port))?;
+        Ok(())
+    }
 
-        // Read response line
-        let mut response_bytes = Vec::new();
-        self.port_read.read_until(b'\n', &mut response_bytes)?; // Read until newline
+    /// Render and write a [`Command`] that expects no reply.
+    fn issue(&mut self, cmd: Command) -> Result<()> {
+        debug_assert!(!cmd.is_query(), "issue() used with a query command: {cmd:?}");
+        self.send(cmd.scpi().as_bytes())
+    }
 
-        // Convert to UTF-8 String
-        let response_str = String::from_utf8(response_bytes)?; // Propagate UTF8 errors
+    /// Render and write a [`Command`], returning the device's [`Response`].
+    fn query(&mut self, cmd: Command) -> Result<Response> {
+        debug_assert!(cmd.is_query(), "query() used with a non-query command: {cmd:?}");
+        Ok(Response::new(self.send_recv(cmd.scpi().as_bytes())?))
+    }
 
-        Ok(response_str) // Port closed automatically when `port` and `buf_reader` go out of scope
+    /// Sends a message and receives a response line, holding the link lock
+    /// across the whole transaction so the reply cannot be claimed by another
+    /// caller on the same line.
+    fn send_recv(&mut self, message: &[u8]) -> Result<String> {
+        let mut link = self.lock();
+        link.send(message)?;
+        link.recv_line()
     }
 
     fn send(&mut self, message: &[u8]) -> Result<()> {
-        // Write message with newline
-        self.port_write.write_all(message)?; // Propagate IO errors
-        self.port_write.write_all(b"\n")?;
-        self.port_write.flush()?; // Ensure data is sent
+        self.lock().send(message)
+    }
+}
 
-        Ok(())
+/// Scalar (`f32`) measure/set API, used when the `units` feature is disabled.
+///
+/// Voltages are in volts, currents in amps, powers in watts.
+#[cfg(not(feature = "units"))]
+impl<T: Transport> Kel103<T> {
+    /// Measure the input voltage.
+    pub fn measure_volt(&mut self) -> Result<f32> {
+        self.measure_volt_raw()
+    }
+
+    /// Measure the *set* (CV mode) voltage level.
+    pub fn measure_set_volt(&mut self) -> Result<f32> {
+        self.measure_set_volt_raw()
+    }
+
+    /// Set the voltage level (CV mode).
+    pub fn set_volt(&mut self, voltage: f32) -> Result<()> {
+        self.set_volt_raw(voltage)
+    }
+
+    /// Measure the input power.
+    pub fn measure_power(&mut self) -> Result<f32> {
+        self.measure_power_raw()
+    }
+
+    /// Measure the *set* power level.
+    pub fn measure_set_power(&mut self) -> Result<f32> {
+        self.measure_set_power_raw()
+    }
+
+    /// Set the power level (CW mode).
+    pub fn set_power(&mut self, power: f32) -> Result<()> {
+        self.set_power_raw(power)
+    }
+
+    /// Measure the input current.
+    pub fn measure_current(&mut self) -> Result<f32> {
+        self.measure_current_raw()
+    }
+
+    /// Measure the *set* current level.
+    pub fn measure_set_current(&mut self) -> Result<f32> {
+        self.measure_set_current_raw()
+    }
+
+    /// Set the current level (CC mode).
+    pub fn set_current(&mut self, current: f32) -> Result<()> {
+        self.set_current_raw(current)
+    }
+}
+
+/// Type-safe measure/set API in terms of `uom` quantities.
+///
+/// Enabled by the `units` feature; SCPI strings are still formatted/parsed in
+/// volts, amps, and watts internally, so a caller can no longer confuse, say,
+/// milliamps for amps. The setters verify the value read back through the
+/// scalar path, so the verification tolerance is the same bare `1e-9` in
+/// volts, amps, or watts; it is not expressed as a quantity.
+#[cfg(feature = "units")]
+impl<T: Transport> Kel103<T> {
+    /// Measure the input voltage.
+    pub fn measure_volt(&mut self) -> Result<ElectricPotential> {
+        Ok(ElectricPotential::new::<volt>(self.measure_volt_raw()?))
+    }
+
+    /// Measure the *set* (CV mode) voltage level.
+    pub fn measure_set_volt(&mut self) -> Result<ElectricPotential> {
+        Ok(ElectricPotential::new::<volt>(self.measure_set_volt_raw()?))
+    }
+
+    /// Set the voltage level (CV mode).
+    pub fn set_volt(&mut self, voltage: ElectricPotential) -> Result<()> {
+        self.set_volt_raw(voltage.get::<volt>())
+    }
+
+    /// Measure the input power.
+    pub fn measure_power(&mut self) -> Result<Power> {
+        Ok(Power::new::<watt>(self.measure_power_raw()?))
+    }
+
+    /// Measure the *set* power level.
+    pub fn measure_set_power(&mut self) -> Result<Power> {
+        Ok(Power::new::<watt>(self.measure_set_power_raw()?))
+    }
+
+    /// Set the power level (CW mode).
+    pub fn set_power(&mut self, power: Power) -> Result<()> {
+        self.set_power_raw(power.get::<watt>())
+    }
+
+    /// Measure the input current.
+    pub fn measure_current(&mut self) -> Result<ElectricCurrent> {
+        Ok(ElectricCurrent::new::<ampere>(self.measure_current_raw()?))
+    }
+
+    /// Measure the *set* current level.
+    pub fn measure_set_current(&mut self) -> Result<ElectricCurrent> {
+        Ok(ElectricCurrent::new::<ampere>(self.measure_set_current_raw()?))
+    }
+
+    /// Set the current level (CC mode).
+    pub fn set_current(&mut self, current: ElectricCurrent) -> Result<()> {
+        self.set_current_raw(current.get::<ampere>())
     }
 }
@@ -0,0 +1,105 @@
+//! Closed-loop PID regulation on top of the `measure_*`/`set_*` primitives.
+//!
+//! A [`Regulator`] drives one actuator setpoint (e.g. [`Kel103::set_current`])
+//! to hold a *measured* quantity (e.g. [`Kel103::measure_volt`]) at a target:
+//! on each step it reads the measurement, computes a PID correction from the
+//! error, and writes the clamped result back through the setter. For example,
+//! regulating a source's output voltage by modulating the load current.
+//!
+//! [`Kel103::set_current`]: crate::Kel103::set_current
+//! [`Kel103::measure_volt`]: crate::Kel103::measure_volt
+
+use crate::{Kel103, Result, Transport};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A single-input single-output PID loop over a [`Kel103`].
+///
+/// The loop reads `measure`, compares it against `setpoint`, and pushes the
+/// clamped controller output through `actuate` on every [`step`](Regulator::step).
+pub struct Regulator<T: Transport> {
+    measure: fn(&mut Kel103<T>) -> Result<f32>,
+    actuate: fn(&mut Kel103<T>, f32) -> Result<()>,
+    setpoint: f32,
+    kp: f32,
+    ki: f32,
+    kd: f32,
+    min: f32,
+    max: f32,
+    integral: f32,
+    prev_error: Option<f32>,
+}
+
+impl<T: Transport> Regulator<T> {
+    /// Create a regulator that holds `measure` at `setpoint` by driving `actuate`.
+    ///
+    /// `min`/`max` clamp the actuator output (and bound the integral term for
+    /// anti-windup). `measure` and `actuate` take the scalar (`f32`) measure/set
+    /// methods directly, e.g.
+    /// `Regulator::new(Kel103::measure_volt, Kel103::set_current, 5.0, ...)`.
+    ///
+    /// Note this requires the default scalar API: with the `units` feature the
+    /// public `measure_*`/`set_*` methods take and return `uom` quantities, so
+    /// they do not match these `f32` function pointers (the `f32` accessors are
+    /// crate-internal), and you must supply your own `f32` adapter functions.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        measure: fn(&mut Kel103<T>) -> Result<f32>,
+        actuate: fn(&mut Kel103<T>, f32) -> Result<()>,
+        setpoint: f32,
+        kp: f32,
+        ki: f32,
+        kd: f32,
+        min: f32,
+        max: f32,
+    ) -> Self {
+        Regulator {
+            measure,
+            actuate,
+            setpoint,
+            kp,
+            ki,
+            kd,
+            min,
+            max,
+            integral: 0.0,
+            prev_error: None,
+        }
+    }
+
+    /// Advance the loop by one `dt`-second step, returning the applied output.
+    pub fn step(&mut self, load: &mut Kel103<T>, dt: f32) -> Result<f32> {
+        let m = (self.measure)(load)?;
+        let error = self.setpoint - m;
+
+        self.integral += error * dt;
+        // Anti-windup: keep ki*integral within the output range.
+        if self.ki != 0.0 {
+            let bound = (self.max / self.ki, self.min / self.ki);
+            let (lo, hi) = (bound.0.min(bound.1), bound.0.max(bound.1));
+            self.integral = self.integral.clamp(lo, hi);
+        }
+
+        let derivative = match self.prev_error {
+            Some(prev) => (error - prev) / dt,
+            None => 0.0,
+        };
+
+        let output =
+            (self.kp * error + self.ki * self.integral + self.kd * derivative).clamp(self.min, self.max);
+        (self.actuate)(load, output)?;
+        self.prev_error = Some(error);
+        Ok(output)
+    }
+
+    /// Run the loop for `duration`, stepping once every `interval`.
+    pub fn run(&mut self, load: &mut Kel103<T>, duration: Duration, interval: Duration) -> Result<()> {
+        let start = Instant::now();
+        let dt = interval.as_secs_f32();
+        while start.elapsed() < duration {
+            self.step(load, dt)?;
+            thread::sleep(interval);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+//! Background telemetry: a reader thread that streams periodic measurements.
+//!
+//! [`Kel103::start_reporting`] spawns a dedicated polling thread that samples
+//! voltage, current, and power at a fixed interval and pushes timestamped
+//! [`Report`]s onto a channel. This lets callers stream live measurements
+//! without hand-rolling a loop, and is the foundation for battery/endurance
+//! logging.
+
+use crate::{Command, Kel103, Response, Result, Transport};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// A single timestamped measurement sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Report {
+    /// Time since reporting started.
+    pub t: Duration,
+    /// The polling interval these samples are produced at.
+    pub interval: Duration,
+    /// Terminal voltage (V).
+    pub volt: f32,
+    /// Input current (A).
+    pub curr: f32,
+    /// Input power (W).
+    pub power: f32,
+}
+
+/// Handle to a running telemetry thread, used to stop it.
+pub(crate) struct ReportHandle {
+    stop: Arc<AtomicBool>,
+    join: Option<JoinHandle<()>>,
+}
+
+impl ReportHandle {
+    /// Signal the thread to stop and wait for it to finish.
+    fn stop(mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(join) = self.join.take() {
+            let _ = join.join();
+        }
+    }
+}
+
+impl<T: Transport + Send + 'static> Kel103<T> {
+    /// Start a background thread that reports voltage, current, and power every
+    /// `interval`, returning the channel samples arrive on.
+    ///
+    /// A previously running reporter is stopped first. The thread exits on its
+    /// own if the receiver is dropped, or when [`stop_reporting`](Kel103::stop_reporting)
+    /// is called.
+    pub fn start_reporting(&mut self, interval: Duration) -> Result<Receiver<Report>> {
+        self.stop_reporting();
+
+        let transport = self.transport();
+        let (tx, rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+
+        let join = thread::spawn(move || {
+            let start = Instant::now();
+            while !thread_stop.load(Ordering::Relaxed) {
+                let sample = (|| -> Result<Report> {
+                    Ok(Report {
+                        t: start.elapsed(),
+                        interval,
+                        volt: query_scalar(&transport, Command::QueryVoltage)?,
+                        curr: query_scalar(&transport, Command::QueryCurrent)?,
+                        power: query_scalar(&transport, Command::QueryPower)?,
+                    })
+                })();
+                match sample {
+                    Ok(report) => {
+                        // Stop if the receiver has hung up.
+                        if tx.send(report).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+                thread::sleep(interval);
+            }
+        });
+
+        self.reporting = Some(ReportHandle {
+            stop,
+            join: Some(join),
+        });
+        Ok(rx)
+    }
+
+    /// Stop the background reporting thread, if one is running.
+    pub fn stop_reporting(&mut self) {
+        if let Some(handle) = self.reporting.take() {
+            handle.stop();
+        }
+    }
+}
+
+/// Run a scalar query over the shared link, serialized against the foreground
+/// handle by the same mutex. The whole send/receive transaction is held under
+/// one lock so the reply is never claimed by another caller.
+fn query_scalar<T: Transport>(transport: &Mutex<T>, cmd: Command) -> Result<f32> {
+    let mut link = transport.lock().unwrap_or_else(|e| e.into_inner());
+    link.send(cmd.scpi().as_bytes())?;
+    Response::new(link.recv_line()?).scalar()
+}
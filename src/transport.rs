@@ -0,0 +1,106 @@
+//! Link-layer abstraction for talking to a KEL103.
+//!
+//! The device speaks the same SCPI dialect over its USB serial port and over
+//! its LAN socket, so the [`Kel103`](crate::Kel103) command methods are written
+//! against this [`Transport`] trait rather than a concrete port type. A
+//! [`SerialTransport`] wraps the USB serial pair; a [`UdpTransport`] speaks to
+//! the device on its configured UDP port.
+
+use crate::{KelError, Result};
+use serialport::{SerialPort, TTYPort};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+/// A byte-oriented, line-delimited link to a KEL103.
+///
+/// Implementors send a raw SCPI frame with [`send`](Transport::send) and read a
+/// single newline-terminated reply with [`recv_line`](Transport::recv_line).
+/// `send` appends the trailing newline at this layer so the command code stays
+/// transport-agnostic; `recv_line` returns the reply as received, terminator
+/// included, and the [`Response`](crate::Response) parsers trim it.
+pub trait Transport {
+    /// Write a SCPI message to the device, terminating it with a newline.
+    fn send(&mut self, message: &[u8]) -> Result<()>;
+
+    /// Read a single newline-terminated response line from the device.
+    fn recv_line(&mut self) -> Result<String>;
+}
+
+/// A [`Transport`] over the device's USB serial port.
+pub struct SerialTransport {
+    port_write: Box<dyn SerialPort>,
+    port_read: BufReader<TTYPort>,
+}
+
+impl SerialTransport {
+    /// Open the serial port at `serial_port` and `baud_rate`.
+    ///
+    /// On Linux serial port should be a path (e.g `/dev/ttyACM0`),
+    /// on windows it will be a port name (e.g `COM0`).
+    pub fn open(serial_port: &str, baud_rate: u32) -> Result<Self> {
+        let port = serialport::new(serial_port, baud_rate)
+            .timeout(Duration::from_secs(1))
+            .open_native()?;
+        let (port_write, port_read) = (port.try_clone()?, BufReader::new(port));
+        Ok(SerialTransport {
+            port_write,
+            port_read,
+        })
+    }
+}
+
+impl Transport for SerialTransport {
+    fn send(&mut self, message: &[u8]) -> Result<()> {
+        self.port_write.write_all(message)?; // Propagate IO errors
+        self.port_write.write_all(b"\n")?;
+        self.port_write.flush()?; // Ensure data is sent
+        Ok(())
+    }
+
+    fn recv_line(&mut self) -> Result<String> {
+        let mut response_bytes = Vec::new();
+        self.port_read.read_until(b'\n', &mut response_bytes)?; // Read until newline
+        let response_str = String::from_utf8(response_bytes)?; // Propagate UTF8 errors
+        Ok(response_str)
+    }
+}
+
+/// A [`Transport`] over the device's LAN (UDP) socket.
+///
+/// The KEL103 answers SCPI datagrams on its configured UDP port; each reply
+/// arrives as a single datagram, so one `recv` yields one response line.
+pub struct UdpTransport {
+    socket: UdpSocket,
+}
+
+impl UdpTransport {
+    /// Connect to the KEL103 listening at `addr` on UDP `port`.
+    pub fn connect(addr: &str, port: u16) -> Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.set_read_timeout(Some(Duration::from_secs(1)))?;
+        let target = (addr, port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| KelError::DeviceError(format!("Could not resolve {}:{}", addr, port)))?;
+        socket.connect(target)?;
+        Ok(UdpTransport { socket })
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send(&mut self, message: &[u8]) -> Result<()> {
+        let mut frame = Vec::with_capacity(message.len() + 1);
+        frame.extend_from_slice(message);
+        frame.push(b'\n');
+        self.socket.send(&frame)?;
+        Ok(())
+    }
+
+    fn recv_line(&mut self) -> Result<String> {
+        let mut buf = [0u8; 512];
+        let n = self.socket.recv(&mut buf)?;
+        let response_str = String::from_utf8(buf[..n].to_vec())?;
+        Ok(response_str)
+    }
+}